@@ -1,9 +1,12 @@
 use anyhow::Result;
 use std::{
+    convert::Infallible,
     env,
     ffi::OsString,
+    fs,
+    io::Write,
     iter::once,
-    path::Path,
+    path::{Path, PathBuf},
     process::{self},
 };
 use tempfile::TempDir;
@@ -29,40 +32,193 @@ impl CheckedCommand for process::Command {
     }
 }
 
+/// Collects options (interpreter, extra `pyvenv.cfg` keys, ...) for creating a [`PyVEnv`]
+#[derive(Default)]
+pub struct PyVEnvBuilder {
+    python: Option<String>,
+    seed: bool,
+    system_site_packages: bool,
+    allow_existing: bool,
+    dir: Option<PathBuf>,
+    extra_cfg: Vec<(String, String)>,
+}
+
+impl PyVEnvBuilder {
+    /// Pin the interpreter, forwarded verbatim to `uv venv --python <x>` (a version like
+    /// `"3.11"` or a path to an interpreter)
+    pub fn python(mut self, python: impl Into<String>) -> Self {
+        self.python = Some(python.into());
+        self
+    }
+
+    /// Pass `--seed` so the created venv ships with `pip`/`setuptools`
+    pub fn seed(mut self, seed: bool) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Pass `--system-site-packages` so the venv can see the base interpreter's packages
+    pub fn system_site_packages(mut self, system_site_packages: bool) -> Self {
+        self.system_site_packages = system_site_packages;
+        self
+    }
+
+    /// Pass `--allow-existing` so creation does not fail if the target already holds a venv
+    pub fn allow_existing(mut self, allow_existing: bool) -> Self {
+        self.allow_existing = allow_existing;
+        self
+    }
+
+    /// Create the venv at this caller-chosen (possibly existing) directory instead of a temp one
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// Append an arbitrary `key = value` line to the generated `pyvenv.cfg` after creation
+    pub fn extra_cfg(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra_cfg.push((key.into(), value.into()));
+        self
+    }
+
+    /// Create the venv, in a temp directory (removed on drop) unless [`Self::dir`] picked one
+    pub fn build(self) -> Result<PyVEnv> {
+        let venv_dir = match &self.dir {
+            Some(_) => None,
+            None => Some(tempfile::tempdir()?),
+        };
+        let root = match (&venv_dir, &self.dir) {
+            (Some(tmp), _) => tmp.path().to_path_buf(),
+            (None, Some(dir)) => dir.clone(),
+            (None, None) => unreachable!(),
+        };
+        let path = PyVEnv::venv_path_var(&root)?;
+
+        let mut command = process::Command::new("uv");
+        command.arg("venv");
+        if let Some(python) = &self.python {
+            command.args(["--python", python]);
+        }
+        if self.seed {
+            command.arg("--seed");
+        }
+        if self.system_site_packages {
+            command.arg("--system-site-packages");
+        }
+        if self.allow_existing {
+            command.arg("--allow-existing");
+        }
+        command.arg(&root).run_checked()?;
+
+        if !self.extra_cfg.is_empty() {
+            let mut cfg = fs::OpenOptions::new().append(true).open(root.join("pyvenv.cfg"))?;
+            for (key, value) in &self.extra_cfg {
+                writeln!(cfg, "{key} = {value}")?;
+            }
+        }
+
+        PyVEnv { venv_dir, root, path }.install(&["pytest", "maturin"])
+    }
+}
+
+/// Outcome of a `pytest` run, pairing the parsed summary counts with the raw process output.
+pub struct PytestResult {
+    /// Number of tests reported as passed by pytest's summary line
+    pub passed: usize,
+    /// Number of tests reported as failed by pytest's summary line
+    pub failed: usize,
+    /// The untouched process output, so callers can inspect stdout/stderr and the exit status
+    pub output: process::Output,
+}
+
+impl PytestResult {
+    /// `true` when pytest exited successfully (no failures, errors, or collection problems)
+    pub fn success(&self) -> bool {
+        self.output.status.success()
+    }
+
+    /// Scrape the `N passed`/`N failed` counts out of pytest's summary line
+    fn parse(output: process::Output) -> Self {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // The summary is the last `==== ... in Ns ====` line; ignore captured test stdout.
+        let summary = stdout.lines().rev().find(|line| line.contains("==") && line.contains(" in ")).unwrap_or("");
+        let count_before = |needle: &str| {
+            summary
+                .split_whitespace()
+                .zip(summary.split_whitespace().skip(1))
+                .find(|(n, word)| word.trim_end_matches(',') == needle && n.parse::<usize>().is_ok())
+                .map(|(n, _)| n.parse().unwrap())
+                .unwrap_or(0)
+        };
+        PytestResult { passed: count_before("passed"), failed: count_before("failed"), output }
+    }
+}
+
 /// Simple type for setting up & running commands within a python venv
 pub struct PyVEnv {
+    // Kept so a temp venv is torn down on drop; the root lives in `root` regardless.
     #[allow(dead_code)]
     venv_dir: Option<TempDir>,
+    root: PathBuf,
     path: OsString,
 }
 
 impl PyVEnv {
     fn get_venv_path(&self) -> &Path {
-        match &self.venv_dir {
-            Some(i) => i.path(),
-            None => Path::new(".venv"),
-        }
+        &self.root
+    }
+
+    /// Build the `PATH` value that puts the given venv's scripts directory first
+    fn venv_path_var(root: &Path) -> Result<OsString> {
+        let venv_scripts_path = root.join(if env::consts::OS == "windows" { "Scripts" } else { "bin" });
+        Ok(env::join_paths(once(venv_scripts_path).chain(env::split_paths(&env::var("PATH")?)))?)
+    }
+
+    /// Start building a venv with a non-default interpreter or extra `pyvenv.cfg` entries
+    pub fn builder() -> PyVEnvBuilder {
+        PyVEnvBuilder::default()
     }
 
     /// Create a new venv in a uniquely named temp directory which will be removed on drop
     pub fn new() -> Result<Self> {
         let venv_dir = tempfile::tempdir()?;
-        let venv_scripts_path = venv_dir.path().join(if env::consts::OS == "windows" { "Scripts" } else { "bin" });
-        let path = env::join_paths(once(venv_scripts_path).chain(env::split_paths(&env::var("PATH")?)))?;
+        let root = venv_dir.path().to_path_buf();
+        let path = Self::venv_path_var(&root)?;
 
-        process::Command::new("uv").args(["venv"]).arg(venv_dir.path()).run_checked()?;
+        process::Command::new("uv").args(["venv"]).arg(&root).run_checked()?;
 
-        PyVEnv { venv_dir: Some(venv_dir), path }.install(&["pytest", "maturin"])
+        PyVEnv { venv_dir: Some(venv_dir), root, path }.install(&["pytest", "maturin"])
     }
 
     /// Create a venv in the local .venv folder. Does *not* overwrite the existing .venv
     pub fn new_persistant() -> Result<Self> {
-        let venv_scripts_path = Path::new(".venv").join(if env::consts::OS == "windows" { "Scripts" } else { "bin" });
-        let path = env::join_paths(once(venv_scripts_path).chain(env::split_paths(&env::var("PATH")?)))?;
+        let root = PathBuf::from(".venv");
+        let path = Self::venv_path_var(&root)?;
 
         process::Command::new("uv").args(["venv", "--seed", "--allow-existing"]).run_checked()?;
 
-        PyVEnv { venv_dir: None, path }.install(&["pytest", "maturin"])
+        PyVEnv { venv_dir: None, root, path }.install(&["pytest", "maturin"])
+    }
+
+    /// Walk upward from the current directory looking for an existing `.venv` to reuse.
+    pub fn discover() -> Result<Self> {
+        Self::discover_from(&env::current_dir()?, usize::MAX)
+    }
+
+    /// Walk up from `start` (at most `max_steps` parents) and reuse the first `.venv` found
+    pub fn discover_from(start: &Path, max_steps: usize) -> Result<Self> {
+        let scripts = if env::consts::OS == "windows" { "Scripts" } else { "bin" };
+        let mut dir = Some(start);
+        for _ in 0..max_steps.saturating_add(1) {
+            let Some(current) = dir else { break };
+            let candidate = current.join(".venv");
+            if candidate.join(scripts).is_dir() {
+                let path = Self::venv_path_var(&candidate)?;
+                return Ok(PyVEnv { venv_dir: None, root: candidate, path });
+            }
+            dir = current.parent();
+        }
+        Err(anyhow::format_err!("No .venv found searching upward from {}", start.display()))
     }
 
     /// Returns a new command with the venv environment configured
@@ -78,6 +234,17 @@ impl PyVEnv {
         Ok(self)
     }
 
+    /// Compile the given requirement inputs into a pinned lockfile at `out` via `uv pip compile`
+    pub fn compile_requirements(&self, inputs: &[&Path], out: &Path) -> Result<()> {
+        self.cmd("uv").args(["pip", "compile"]).args(inputs).arg("-o").arg(out).run_checked()?;
+        Ok(())
+    }
+
+    /// Sync the venv to exactly match `lockfile` via `uv pip sync` (installs missing, removes extra)
+    pub fn sync(self, lockfile: &Path) -> Result<Self> {
+        self.cmd("uv").args(["pip", "sync"]).arg(lockfile).run_checked().map(|_| self)
+    }
+
     /// Execute maturin develop in the current directory
     pub fn maturin_develop(self) -> Result<Self> {
         self.add_maturin_dep(Path::new("."))
@@ -88,28 +255,170 @@ impl PyVEnv {
         self.cmd("maturin").current_dir(path).args(["develop", "--uv"]).run_checked().map(|_| self)
     }
 
+    /// Replace the current process with a venv command (execvp on unix, a Job Object on windows)
+    #[cfg(unix)]
+    pub fn exec(self, cmd: &str, args: &[&str]) -> Result<Infallible> {
+        use std::os::unix::process::CommandExt;
+        // exec only returns if it failed to replace the image.
+        Err(self.cmd(cmd).args(args).exec().into())
+    }
+
+    #[cfg(windows)]
+    pub fn exec(self, cmd: &str, args: &[&str]) -> Result<Infallible> {
+        use std::{os::windows::io::AsRawHandle, ptr};
+        use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+        use windows_sys::Win32::System::JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, SetInformationJobObject,
+            JobObjectExtendedLimitInformation, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        };
+
+        // Closes the Job Object on every exit path, including early `?` returns.
+        struct JobHandle(HANDLE);
+        impl Drop for JobHandle {
+            fn drop(&mut self) {
+                unsafe { CloseHandle(self.0) };
+            }
+        }
+
+        // SAFETY: raw Win32 calls; handles are checked before use and the job outlives the child.
+        unsafe {
+            let job_handle = CreateJobObjectW(ptr::null(), ptr::null());
+            if job_handle.is_null() {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            let job = JobHandle(job_handle);
+
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            if SetInformationJobObject(
+                job.0,
+                JobObjectExtendedLimitInformation,
+                ptr::addr_of!(info).cast(),
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            ) == 0
+            {
+                return Err(std::io::Error::last_os_error().into());
+            }
+
+            let mut child = self.cmd(cmd).args(args).spawn()?;
+            if AssignProcessToJobObject(job.0, child.as_raw_handle() as _) == 0 {
+                // The kill-on-close guarantee cannot be honoured, so don't pretend it holds.
+                let err = std::io::Error::last_os_error();
+                let _ = child.kill();
+                return Err(err.into());
+            }
+            let status = match child.wait() {
+                Ok(status) => status,
+                Err(err) => {
+                    let _ = child.kill();
+                    return Err(err.into());
+                }
+            };
+            // process::exit skips destructors, so release the temp venv dir and the
+            // job handle ourselves before tearing down the process.
+            drop(job);
+            drop(self);
+            process::exit(status.code().unwrap_or(1));
+        }
+    }
+
     /// Execute a python module
     pub fn run_module(&self, module: &str, args: &[&str]) -> Result<process::Output> {
         self.cmd("python").arg("-m").arg(module).args(args).run_checked()
     }
 
-    /// Run pytest
-    pub fn run_pytest(&self) -> Result<()> {
+    /// Run a one-off tool via `uv tool run` (`uvx`) without installing it into the venv; a non-zero exit is not an `Err`
+    pub fn tool_run(&self, tool: &str, args: &[&str]) -> Result<process::Output> {
+        Ok(self.cmd("uv").args(["tool", "run", tool]).args(args).output()?)
+    }
+
+    /// Run pytest over the given paths, returning the parsed result; a non-zero/failing exit is not an `Err`
+    pub fn run_pytest(&self, paths: &[&Path], extra_args: &[&str]) -> Result<PytestResult> {
         // Pass -s to ensure that on failure we capture *all* test output
         // Without this, rust panic backtraces are swollowed
-        self.cmd("pytest").arg("--version").run_checked()?;
-        Ok(())
+        let output =
+            self.cmd("pytest").arg("-s").args(paths).args(extra_args).output()?;
+        Ok(PytestResult::parse(output))
+    }
+
+    /// Convenience wrapper that runs pytest against the current directory
+    pub fn run_pytest_cwd(&self) -> Result<PytestResult> {
+        self.run_pytest(&[Path::new(".")], &[])
     }
 }
 
 #[cfg(test)]
 mod test {
     use anyhow::Result;
-    use crate::PyVEnv;
+    use crate::{PyVEnv, PytestResult};
+    use std::process;
 
     #[test]
     fn run_pytest() -> Result<()> {
-        PyVEnv::new()?
-            .run_pytest()
+        PyVEnv::new()?.run_pytest_cwd()?;
+        Ok(())
+    }
+
+    fn output_with_stdout(stdout: &str) -> process::Output {
+        process::Output { status: exit_status(0), stdout: stdout.as_bytes().to_vec(), stderr: Vec::new() }
+    }
+
+    #[cfg(unix)]
+    fn exit_status(code: i32) -> process::ExitStatus {
+        use std::os::unix::process::ExitStatusExt;
+        process::ExitStatus::from_raw(code)
+    }
+
+    #[cfg(windows)]
+    fn exit_status(code: i32) -> process::ExitStatus {
+        use std::os::windows::process::ExitStatusExt;
+        process::ExitStatus::from_raw(code as u32)
+    }
+
+    #[test]
+    fn parse_reads_passed_and_failed_counts() {
+        let result = PytestResult::parse(output_with_stdout("===== 1 failed, 3 passed in 0.12s =====\n"));
+        assert_eq!(result.passed, 3);
+        assert_eq!(result.failed, 1);
+    }
+
+    #[test]
+    fn parse_reads_all_passed() {
+        let result = PytestResult::parse(output_with_stdout("===== 5 passed in 0.01s =====\n"));
+        assert_eq!(result.passed, 5);
+        assert_eq!(result.failed, 0);
+    }
+
+    #[test]
+    fn parse_ignores_captured_output_resembling_a_summary_line() {
+        let stdout = "captured stdout\n\
+            result == expected, ok in this test\n\
+            ==== not a summary line ====\n\
+            ===== 2 passed in 0.02s =====\n";
+        let result = PytestResult::parse(output_with_stdout(stdout));
+        assert_eq!(result.passed, 2);
+        assert_eq!(result.failed, 0);
+    }
+
+    #[test]
+    fn discover_walks_upward() -> Result<()> {
+        let root = tempfile::tempdir()?;
+        let scripts = if std::env::consts::OS == "windows" { "Scripts" } else { "bin" };
+        std::fs::create_dir_all(root.path().join(".venv").join(scripts))?;
+        let nested = root.path().join("a").join("b");
+        std::fs::create_dir_all(&nested)?;
+
+        let venv = PyVEnv::discover_from(&nested, 8)?;
+        assert_eq!(venv.get_venv_path(), root.path().join(".venv"));
+        Ok(())
+    }
+
+    #[test]
+    fn extra_cfg_is_written() -> Result<()> {
+        let venv = PyVEnv::builder().extra_cfg("pyo3_venv_marker", "stamped").build()?;
+        let cfg = std::fs::read_to_string(venv.get_venv_path().join("pyvenv.cfg"))?;
+        assert!(cfg.contains("pyo3_venv_marker = stamped"));
+        Ok(())
     }
 }